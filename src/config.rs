@@ -1,11 +1,58 @@
 //! Application configuration from environment variables.
 
+use std::collections::HashMap;
 use std::env;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+
+/// Where the server should bind and listen. Set via `LISTEN_ADDR`, either
+/// `tcp://host:port` or `unix:/path/to.sock`.
+#[derive(Debug, Clone)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    fn parse(s: &str, default_port: u16) -> Self {
+        if let Some(path) = s.strip_prefix("unix:") {
+            return ListenAddr::Unix(PathBuf::from(path));
+        }
+
+        if let Some(rest) = s.strip_prefix("tcp://") {
+            let resolved = rest.to_socket_addrs().and_then(|mut addrs| {
+                addrs.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses resolved")
+                })
+            });
+            match resolved {
+                Ok(addr) => return ListenAddr::Tcp(addr),
+                Err(e) => {
+                    tracing::warn!(
+                        "invalid LISTEN_ADDR '{}': {}, falling back to tcp port {}",
+                        s,
+                        e,
+                        default_port
+                    );
+                    return ListenAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], default_port)));
+                }
+            }
+        }
+
+        tracing::warn!(
+            "LISTEN_ADDR '{}' has no recognized 'unix:' or 'tcp://' prefix, falling back to tcp port {}",
+            s,
+            default_port
+        );
+        ListenAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], default_port)))
+    }
+}
 
 /// Application configuration loaded from environment.
 #[derive(Debug, Clone)]
 pub struct Config {
     pub port: u16,
+    pub listen_addr: ListenAddr,
     pub store_name: String,
     pub otel_exporter_endpoint: Option<String>,
     pub otel_service_name: String,
@@ -22,6 +69,11 @@ impl Config {
             .and_then(|s| s.parse().ok())
             .unwrap_or(8080);
 
+        let listen_addr = env::var("LISTEN_ADDR")
+            .ok()
+            .map(|s| ListenAddr::parse(&s, port))
+            .unwrap_or(ListenAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], port))));
+
         let store_name = env::var("STATESTORE_NAME").unwrap_or_else(|_| "statestore".to_string());
 
         let otel_exporter_endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok();
@@ -31,9 +83,59 @@ impl Config {
 
         Self {
             port,
+            listen_addr,
             store_name,
             otel_exporter_endpoint,
             otel_service_name,
         }
     }
 }
+
+/// Settings that can be retuned while the process is running, without a
+/// restart. Held behind an `Arc<ArcSwap<RuntimeConfig>>` created in `main.rs`
+/// and swapped in by the `.env` watcher spawned there; it is not part of
+/// `AppState`, so handlers do not read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeConfig {
+    pub log_directive: String,
+    pub otel_metric_export_interval_secs: u64,
+    pub broadcast_capacity: usize,
+}
+
+impl RuntimeConfig {
+    /// Build from the current process environment.
+    pub fn from_env() -> Self {
+        Self::from_vars(&env::vars().collect())
+    }
+
+    /// Build directly from a `.env`-style file, independent of the process
+    /// environment, so the watcher can see edits without the process having
+    /// re-sourced them.
+    pub fn from_file(path: &Path) -> Self {
+        let vars = dotenvy::from_path_iter(path)
+            .map(|iter| iter.filter_map(Result::ok).collect())
+            .unwrap_or_default();
+        Self::from_vars(&vars)
+    }
+
+    fn from_vars(vars: &HashMap<String, String>) -> Self {
+        Self {
+            log_directive: vars
+                .get("LOG_LEVEL")
+                .or_else(|| vars.get("RUST_LOG"))
+                .cloned()
+                .unwrap_or_else(|| "info".to_string()),
+            otel_metric_export_interval_secs: vars
+                .get("OTEL_METRIC_EXPORT_INTERVAL_SECS")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10),
+            // `tokio::sync::broadcast::channel` panics on capacity 0, so
+            // reject it here rather than at the call site.
+            broadcast_capacity: vars
+                .get("BROADCAST_CAPACITY")
+                .and_then(|s| s.parse::<usize>().ok())
+                .filter(|&capacity| capacity >= 1)
+                .unwrap_or(1024),
+        }
+    }
+}
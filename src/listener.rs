@@ -0,0 +1,137 @@
+//! A listener that accepts connections over either a TCP socket or a Unix
+//! domain socket, so `axum::serve` can be driven the same way regardless of
+//! which the deployment configures via `LISTEN_ADDR`.
+
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+
+use crate::config::ListenAddr;
+
+/// A bound TCP or Unix domain socket listener.
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl Listener {
+    /// Bind according to the configured [`ListenAddr`]. For a Unix socket,
+    /// removes any stale socket file left behind by a previous run and
+    /// restricts permissions to owner/group read-write (0660).
+    pub async fn bind(addr: &ListenAddr) -> io::Result<Self> {
+        match addr {
+            ListenAddr::Tcp(socket_addr) => {
+                Ok(Listener::Tcp(TcpListener::bind(socket_addr).await?))
+            }
+            ListenAddr::Unix(path) => {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)?;
+                set_permissions(path, 0o660)?;
+                Ok(Listener::Unix(listener))
+            }
+        }
+    }
+}
+
+fn set_permissions(path: &Path, mode: u32) -> io::Result<()> {
+    let mut perms = std::fs::metadata(path)?.permissions();
+    perms.set_mode(mode);
+    std::fs::set_permissions(path, perms)
+}
+
+impl axum::serve::Listener for Listener {
+    type Io = IoStream;
+    type Addr = IoAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            let accepted = match self {
+                Listener::Tcp(listener) => listener
+                    .accept()
+                    .await
+                    .map(|(io, addr)| (IoStream::Tcp(io), IoAddr::Tcp(addr))),
+                Listener::Unix(listener) => listener
+                    .accept()
+                    .await
+                    .map(|(io, addr)| (IoStream::Unix(io), IoAddr::Unix(addr))),
+            };
+
+            match accepted {
+                Ok(accepted) => return accepted,
+                // Transient accept errors (e.g. hitting the open-fd limit)
+                // shouldn't take the whole server down; log and retry.
+                Err(e) => {
+                    tracing::error!("accept error: {}", e);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().map(IoAddr::Tcp),
+            Listener::Unix(listener) => listener.local_addr().map(IoAddr::Unix),
+        }
+    }
+}
+
+/// An accepted connection from either socket kind.
+pub enum IoStream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl AsyncRead for IoStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            IoStream::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            IoStream::Unix(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for IoStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            IoStream::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            IoStream::Unix(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            IoStream::Tcp(s) => Pin::new(s).poll_flush(cx),
+            IoStream::Unix(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            IoStream::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            IoStream::Unix(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+/// The peer address of an accepted connection, from either socket kind.
+pub enum IoAddr {
+    Tcp(std::net::SocketAddr),
+    Unix(tokio::net::unix::SocketAddr),
+}
@@ -1,33 +1,45 @@
 //! Example Rust app with Dapr state store and OpenTelemetry.
 
 mod config;
+mod listener;
 mod server;
 mod telemetry;
 
-use std::net::SocketAddr;
+use arc_swap::ArcSwap;
+use config::RuntimeConfig;
+use listener::Listener;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
 use tracing::info;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+const ENV_FILE: &str = ".env";
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let cfg = config::Config::load();
+    let runtime_config = Arc::new(ArcSwap::new(Arc::new(RuntimeConfig::from_env())));
 
-    // Initialize tracing (JSON logs to stdout)
+    // Initialize tracing (JSON logs to stdout) behind a reload handle, so the
+    // config watcher can retune verbosity without restarting the process.
     // When OTEL is configured, tracing spans will be exported via the global tracer provider
-    tracing_subscriber::fmt()
-        .json()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env().add_directive("info".parse()?),
-        )
+    let (filter_layer, filter_handle) = tracing_subscriber::reload::Layer::new(
+        tracing_subscriber::EnvFilter::new(&runtime_config.load().log_directive),
+    );
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer().json())
         .init();
 
     // Initialize OpenTelemetry
     let shutdown_telemetry = telemetry::init(
         cfg.otel_exporter_endpoint.as_deref(),
         &cfg.otel_service_name,
+        runtime_config.load().otel_metric_export_interval_secs,
     );
 
     // Wait for Dapr sidecar when DAPR_GRPC_PORT is set
@@ -47,17 +59,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     };
 
-    let app_state = server::AppState {
+    let app_state = server::AppState::new(
         state_client,
-        store_name: cfg.store_name.clone(),
-    };
+        cfg.store_name.clone(),
+        runtime_config.load().broadcast_capacity.max(1),
+    );
+
+    spawn_config_watcher(
+        cfg.port,
+        cfg.otel_exporter_endpoint.clone(),
+        cfg.otel_service_name.clone(),
+        runtime_config.clone(),
+        app_state.events.clone(),
+        filter_handle,
+    );
 
     let app = server::router(app_state).layer(TraceLayer::new_for_http());
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], cfg.port));
-    let listener = TcpListener::bind(addr).await?;
+    let listener = Listener::bind(&cfg.listen_addr).await?;
 
-    info!(port = cfg.port, store = cfg.store_name, "server starting");
+    info!(
+        listen_addr = ?cfg.listen_addr,
+        store = cfg.store_name,
+        "server starting"
+    );
 
     axum::serve(listener, app)
         .with_graceful_shutdown(shutdown_signal())
@@ -69,6 +94,102 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     Ok(())
 }
 
+/// Watch `.env` for changes and hot-swap the runtime-tunable settings it
+/// carries (log level, OTLP metric export interval, broadcast channel
+/// capacity). Immutable settings like `APP_PORT` are logged as requiring a
+/// restart rather than applied.
+fn spawn_config_watcher(
+    fixed_port: u16,
+    otel_exporter_endpoint: Option<String>,
+    otel_service_name: String,
+    runtime_config: Arc<ArcSwap<RuntimeConfig>>,
+    events: Arc<ArcSwap<tokio::sync::broadcast::Sender<server::StateEvent>>>,
+    filter_handle: tracing_subscriber::reload::Handle<
+        tracing_subscriber::EnvFilter,
+        tracing_subscriber::Registry,
+    >,
+) {
+    tokio::spawn(async move {
+        let path = Path::new(ENV_FILE);
+        let mut last_modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        loop {
+            tokio::time::sleep(CONFIG_WATCH_INTERVAL).await;
+
+            let modified = match std::fs::metadata(path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue, // no .env file present to watch
+            };
+            if Some(modified) == last_modified {
+                continue;
+            }
+            last_modified = Some(modified);
+
+            if let Ok(vars) = dotenvy::from_path_iter(path).map(|iter| {
+                iter.filter_map(Result::ok)
+                    .collect::<std::collections::HashMap<_, _>>()
+            }) {
+                if let Some(new_port) = vars.get("APP_PORT").and_then(|s| s.parse::<u16>().ok()) {
+                    if new_port != fixed_port {
+                        tracing::warn!(
+                            current = fixed_port,
+                            requested = new_port,
+                            "APP_PORT changed in .env; restart the process to apply it"
+                        );
+                    }
+                }
+            }
+
+            let next = RuntimeConfig::from_file(path);
+            let current = runtime_config.load();
+            if *current == next {
+                continue;
+            }
+
+            if next.log_directive != current.log_directive {
+                match tracing_subscriber::EnvFilter::try_new(&next.log_directive) {
+                    Ok(filter) => match filter_handle.reload(filter) {
+                        Ok(()) => info!(directive = %next.log_directive, "log level reloaded"),
+                        Err(e) => tracing::error!("failed to reload log filter: {}", e),
+                    },
+                    Err(e) => tracing::error!(
+                        "invalid log directive '{}' in .env: {}",
+                        next.log_directive,
+                        e
+                    ),
+                }
+            }
+
+            if next.otel_metric_export_interval_secs != current.otel_metric_export_interval_secs {
+                telemetry::reload_meter_interval(
+                    otel_exporter_endpoint.as_deref(),
+                    &otel_service_name,
+                    next.otel_metric_export_interval_secs,
+                );
+                info!(
+                    interval_secs = next.otel_metric_export_interval_secs,
+                    "OTEL metric export interval reloaded"
+                );
+            }
+
+            if next.broadcast_capacity != current.broadcast_capacity {
+                // `broadcast::channel` panics on capacity 0; `RuntimeConfig`
+                // already rejects that when parsing, but guard here too
+                // since this is the call site that would actually panic.
+                let capacity = next.broadcast_capacity.max(1);
+                let (sender, _) = tokio::sync::broadcast::channel(capacity);
+                events.store(Arc::new(sender));
+                info!(
+                    capacity,
+                    "state event broadcast channel recreated with new capacity; existing SSE subscribers will stop receiving events"
+                );
+            }
+
+            runtime_config.store(Arc::new(next));
+        }
+    });
+}
+
 /// Poll Dapr outbound health until ready or timeout.
 async fn wait_for_dapr() {
     let port = std::env::var("DAPR_HTTP_PORT").unwrap_or_else(|_| "3500".to_string());
@@ -4,12 +4,27 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
-use super::StateClient;
+use super::{ConcurrencyMode, StateClient, StateWriteError, TransactionOp};
+
+/// A stored value alongside a monotonically increasing version, formatted
+/// as the ETag string, so optimistic-concurrency semantics work in local
+/// dev without Dapr.
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Vec<u8>,
+    version: u64,
+}
+
+impl Entry {
+    fn etag(&self) -> String {
+        self.version.to_string()
+    }
+}
 
 /// In-memory state store for local dev when Dapr is unavailable.
 #[derive(Debug, Default)]
 pub struct MemStore {
-    data: RwLock<HashMap<String, Vec<u8>>>,
+    data: RwLock<HashMap<String, Entry>>,
 }
 
 impl MemStore {
@@ -20,43 +35,112 @@ impl MemStore {
     }
 }
 
+/// Checks whether a write may proceed given the entry's current etag (if
+/// any), the caller-supplied `etag` and `concurrency` mode.
+fn check_etag(current: Option<&str>, etag: Option<&str>, concurrency: ConcurrencyMode) -> bool {
+    match concurrency {
+        ConcurrencyMode::LastWriteWins => true,
+        ConcurrencyMode::FirstWriteWins => match etag {
+            None => true,
+            Some(etag) => current == Some(etag),
+        },
+    }
+}
+
 #[async_trait]
 impl StateClient for MemStore {
     async fn get_state(
         &self,
         _store: &str,
         key: &str,
-    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<(Vec<u8>, Option<String>)>, Box<dyn std::error::Error + Send + Sync>> {
         let data = self
             .data
             .read()
             .map_err(|e| format!("lock poisoned: {}", e))?;
-        Ok(data.get(key).cloned())
+        Ok(data.get(key).map(|entry| (entry.value.clone(), Some(entry.etag()))))
     }
 
-    async fn save_state(
+    async fn get_bulk_state(
         &self,
         _store: &str,
-        key: &str,
-        value: Vec<u8>,
+        keys: &[String],
+    ) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error + Send + Sync>> {
+        let data = self
+            .data
+            .read()
+            .map_err(|e| format!("lock poisoned: {}", e))?;
+        Ok(keys
+            .iter()
+            .map(|key| data.get(key).map(|entry| entry.value.clone()))
+            .collect())
+    }
+
+    async fn execute_state_transaction(
+        &self,
+        _store: &str,
+        ops: Vec<TransactionOp>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Held for the whole transaction so the batch is applied atomically:
+        // no other request observes a partial set of these writes.
         let mut data = self
             .data
             .write()
             .map_err(|e| format!("lock poisoned: {}", e))?;
-        data.insert(key.to_string(), value);
+        for op in ops {
+            match op {
+                TransactionOp::Upsert { key, value } => {
+                    let version = data.get(&key).map_or(1, |entry| entry.version + 1);
+                    data.insert(key, Entry { value, version });
+                }
+                TransactionOp::Delete { key } => {
+                    data.remove(&key);
+                }
+            }
+        }
         Ok(())
     }
 
-    async fn delete_state(
+    async fn save_state_with_etag(
         &self,
         _store: &str,
         key: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        value: Vec<u8>,
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError> {
         let mut data = self
             .data
             .write()
-            .map_err(|e| format!("lock poisoned: {}", e))?;
+            .map_err(|e| StateWriteError::Other(format!("lock poisoned: {}", e).into()))?;
+
+        let current = data.get(key).map(|entry| entry.etag());
+        if !check_etag(current.as_deref(), etag.as_deref(), concurrency) {
+            return Err(StateWriteError::EtagMismatch);
+        }
+
+        let version = data.get(key).map_or(1, |entry| entry.version + 1);
+        data.insert(key.to_string(), Entry { value, version });
+        Ok(())
+    }
+
+    async fn delete_state_with_etag(
+        &self,
+        _store: &str,
+        key: &str,
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError> {
+        let mut data = self
+            .data
+            .write()
+            .map_err(|e| StateWriteError::Other(format!("lock poisoned: {}", e).into()))?;
+
+        let current = data.get(key).map(|entry| entry.etag());
+        if !check_etag(current.as_deref(), etag.as_deref(), concurrency) {
+            return Err(StateWriteError::EtagMismatch);
+        }
+
         data.remove(key);
         Ok(())
     }
@@ -0,0 +1,111 @@
+//! Per-request RED (rate/errors/duration) metrics and span enrichment.
+//!
+//! Wired up as an `axum::middleware::from_fn` layer in [`super::router`], so
+//! every request is accounted for regardless of which handler serves it.
+
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::{global, KeyValue};
+use tracing::Instrument;
+
+// Instruments are taken from the current global meter provider on each use,
+// so a provider installed later by `telemetry::reload_meter_interval` receives
+// the measurements.
+fn request_counter() -> Counter<u64> {
+    global::meter("example-rust-dapr-otel")
+        .u64_counter("http.server.requests")
+        .with_description("Number of HTTP requests handled")
+        .init()
+}
+
+fn duration_histogram() -> Histogram<f64> {
+    global::meter("example-rust-dapr-otel")
+        .f64_histogram("http.server.duration")
+        .with_unit("s")
+        .with_description("Duration of HTTP requests in seconds")
+        .init()
+}
+
+/// Records the request duration (and increments the request counter) when
+/// dropped, so a panic or early return inside the handler still produces a
+/// measurement.
+struct DurationRecorder {
+    start: Instant,
+    method: String,
+    route: String,
+    status_code: AtomicU16,
+}
+
+impl DurationRecorder {
+    fn set_status(&self, status_code: u16) {
+        self.status_code.store(status_code, Ordering::Relaxed);
+    }
+}
+
+impl Drop for DurationRecorder {
+    fn drop(&mut self) {
+        let attrs = [
+            KeyValue::new("http.method", self.method.clone()),
+            KeyValue::new("http.route", self.route.clone()),
+            KeyValue::new(
+                "http.status_code",
+                self.status_code.load(Ordering::Relaxed) as i64,
+            ),
+        ];
+        duration_histogram().record(self.start.elapsed().as_secs_f64(), &attrs);
+        request_counter().add(1, &attrs);
+    }
+}
+
+/// Extracts the `:key` path parameter from a route pattern like
+/// `/api/v1/state/:key`, taking the last segment of the request path, so it
+/// can be attached to the active span alongside the matched route.
+fn state_key(route: &str, path: &str) -> Option<String> {
+    if route.ends_with("/:key") {
+        path.rsplit('/').next().map(str::to_string)
+    } else {
+        None
+    }
+}
+
+pub async fn record_metrics(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let key = state_key(&route, req.uri().path());
+
+    let span = tracing::info_span!(
+        "http.request",
+        http.method = %method,
+        http.route = %route,
+        http.status_code = tracing::field::Empty,
+        key = tracing::field::Empty,
+    );
+    if let Some(key) = &key {
+        span.record("key", key.as_str());
+    }
+
+    let recorder = DurationRecorder {
+        start: Instant::now(),
+        method,
+        route,
+        status_code: AtomicU16::new(0),
+    };
+
+    let response = next.run(req).instrument(span.clone()).await;
+
+    span.record("http.status_code", response.status().as_u16());
+    recorder.set_status(response.status().as_u16());
+
+    response
+}
@@ -1,40 +1,132 @@
 //! HTTP server and state management.
 
 mod memstore;
+mod metrics;
 
 use axum::{
     body::Body,
     extract::{Path, State},
     http::{header, StatusCode},
-    response::IntoResponse,
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{delete, get, post},
-    Router,
+    Json, Router,
 };
+use arc_swap::ArcSwap;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
 use tracing::error;
 
 pub use memstore::MemStore;
 
+/// Kind of mutation that produced a [`StateEvent`].
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateOp {
+    Save,
+    Delete,
+}
+
+/// Notification published on the broadcast channel after a successful
+/// state mutation.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateEvent {
+    pub key: String,
+    pub op: StateOp,
+    pub len: usize,
+}
+
+/// A single operation within an atomic multi-key state transaction.
+#[derive(Debug, Clone)]
+pub enum TransactionOp {
+    Upsert { key: String, value: Vec<u8> },
+    Delete { key: String },
+}
+
+/// Optimistic-concurrency mode for a conditional write, mirroring Dapr's
+/// state concurrency options.
+#[derive(Debug, Clone, Copy)]
+pub enum ConcurrencyMode {
+    /// Reject the write if the stored ETag has moved on since it was read.
+    FirstWriteWins,
+    /// Write regardless of the current ETag.
+    LastWriteWins,
+}
+
+/// Error from a conditional write against a state store.
+#[derive(Debug)]
+pub enum StateWriteError {
+    /// The ETag no longer matches the stored value.
+    EtagMismatch,
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Display for StateWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateWriteError::EtagMismatch => write!(f, "etag mismatch"),
+            StateWriteError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StateWriteError {}
+
 /// Trait for state store operations (Dapr or in-memory).
 #[async_trait::async_trait]
 pub trait StateClient: Send + Sync {
+    /// Returns the stored value alongside its current ETag, if the store
+    /// tracks one.
     async fn get_state(
         &self,
         store: &str,
         key: &str,
-    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>;
-    async fn save_state(
+    ) -> Result<Option<(Vec<u8>, Option<String>)>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Fetch several keys in one round trip. Results are returned in the
+    /// same order as `keys`; a key with no stored value is `None`.
+    async fn get_bulk_state(
+        &self,
+        store: &str,
+        keys: &[String],
+    ) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Apply a set of upserts/deletes atomically: either every operation
+    /// takes effect or none does.
+    async fn execute_state_transaction(
+        &self,
+        store: &str,
+        ops: Vec<TransactionOp>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Save a value, honoring `etag` under the given `concurrency` mode.
+    /// `etag: None` skips the concurrency check entirely.
+    async fn save_state_with_etag(
         &self,
         store: &str,
         key: &str,
         value: Vec<u8>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-    async fn delete_state(
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError>;
+
+    /// Delete a value, honoring `etag` under the given `concurrency` mode.
+    async fn delete_state_with_etag(
         &self,
         store: &str,
         key: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError>;
 }
 
 /// Dapr client wrapper implementing StateClient.
@@ -56,49 +148,153 @@ impl StateClient for DaprStateClient {
         &self,
         store: &str,
         key: &str,
-    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Option<(Vec<u8>, Option<String>)>, Box<dyn std::error::Error + Send + Sync>> {
         let mut client = self.client.lock().await;
         let response = client
             .get_state(store, key, None)
             .await
             .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
         let data = response.data;
-        Ok(if data.is_empty() { None } else { Some(data) })
+        if data.is_empty() {
+            Ok(None)
+        } else {
+            let etag = if response.etag.is_empty() {
+                None
+            } else {
+                Some(response.etag)
+            };
+            Ok(Some((data, etag)))
+        }
     }
 
-    async fn save_state(
+    async fn get_bulk_state(
         &self,
         store: &str,
-        key: &str,
-        value: Vec<u8>,
+        keys: &[String],
+    ) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut client = self.client.lock().await;
+        let response = client
+            .get_bulk_state(store, keys.to_vec(), None)
+            .await
+            .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
+
+        let by_key: std::collections::HashMap<String, Vec<u8>> = response
+            .items
+            .into_iter()
+            .filter(|item| item.error.is_empty() && !item.data.is_empty())
+            .map(|item| (item.key, item.data))
+            .collect();
+
+        Ok(keys.iter().map(|key| by_key.get(key).cloned()).collect())
+    }
+
+    async fn execute_state_transaction(
+        &self,
+        store: &str,
+        ops: Vec<TransactionOp>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let operations = ops
+            .into_iter()
+            .map(|op| match op {
+                TransactionOp::Upsert { key, value } => {
+                    dapr::client::TransactionalStateOperation::upsert(key, value)
+                }
+                TransactionOp::Delete { key } => {
+                    dapr::client::TransactionalStateOperation::delete(key)
+                }
+            })
+            .collect();
+
         let mut client = self.client.lock().await;
         client
-            .save_state(store, key, value, None, None, None)
+            .execute_state_transaction(store, operations)
             .await
             .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
         Ok(())
     }
 
-    async fn delete_state(
+    async fn save_state_with_etag(
         &self,
         store: &str,
         key: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        value: Vec<u8>,
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError> {
+        let options = dapr::client::StateOptions {
+            concurrency: Some(concurrency.into()),
+            consistency: None,
+        };
         let mut client = self.client.lock().await;
         client
-            .delete_state(store, key, None)
+            .save_state(store, key, value, etag, Some(options), None)
             .await
-            .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
+            .map_err(classify_dapr_error)?;
+        Ok(())
+    }
+
+    async fn delete_state_with_etag(
+        &self,
+        store: &str,
+        key: &str,
+        etag: Option<String>,
+        concurrency: ConcurrencyMode,
+    ) -> Result<(), StateWriteError> {
+        let options = dapr::client::StateOptions {
+            concurrency: Some(concurrency.into()),
+            consistency: None,
+        };
+        let mut client = self.client.lock().await;
+        client
+            .delete_state(store, key, Some(options))
+            .await
+            .map_err(classify_dapr_error)?;
         Ok(())
     }
 }
 
+impl From<ConcurrencyMode> for dapr::client::StateOptionsConcurrency {
+    fn from(mode: ConcurrencyMode) -> Self {
+        match mode {
+            ConcurrencyMode::FirstWriteWins => dapr::client::StateOptionsConcurrency::FirstWrite,
+            ConcurrencyMode::LastWriteWins => dapr::client::StateOptionsConcurrency::LastWrite,
+        }
+    }
+}
+
+/// Dapr surfaces an ETag conflict as a failed-precondition gRPC status;
+/// everything else is an ordinary store error.
+fn classify_dapr_error(e: impl std::error::Error + Send + Sync + 'static) -> StateWriteError {
+    let message = e.to_string().to_lowercase();
+    if message.contains("etag") || message.contains("precondition") {
+        StateWriteError::EtagMismatch
+    } else {
+        StateWriteError::Other(Box::new(e))
+    }
+}
+
 /// App state shared across handlers.
 #[derive(Clone)]
 pub struct AppState {
     pub state_client: Arc<dyn StateClient>,
     pub store_name: String,
+    /// Swapped out by the config watcher when `BROADCAST_CAPACITY` changes;
+    /// existing subscribers stop receiving events on a swap since tokio's
+    /// broadcast channel capacity is fixed at creation.
+    pub events: Arc<ArcSwap<broadcast::Sender<StateEvent>>>,
+}
+
+impl AppState {
+    /// Build app state, wiring up the state-change event broadcast channel
+    /// at the given initial capacity.
+    pub fn new(state_client: Arc<dyn StateClient>, store_name: String, broadcast_capacity: usize) -> Self {
+        let (events, _) = broadcast::channel(broadcast_capacity);
+        Self {
+            state_client,
+            store_name,
+            events: Arc::new(ArcSwap::new(Arc::new(events))),
+        }
+    }
 }
 
 pub fn router(state: AppState) -> Router {
@@ -106,9 +302,13 @@ pub fn router(state: AppState) -> Router {
         .route("/livez", get(livez))
         .route("/readyz", get(readyz))
         .route("/health", get(health))
+        .route("/api/v1/state/events", get(state_events))
+        .route("/api/v1/state/bulk", post(bulk_get_state))
+        .route("/api/v1/state/transaction", post(execute_transaction))
         .route("/api/v1/state/:key", get(get_state))
         .route("/api/v1/state/:key", post(save_state))
         .route("/api/v1/state/:key", delete(delete_state))
+        .route_layer(middleware::from_fn(metrics::record_metrics))
         .with_state(state)
 }
 
@@ -124,18 +324,44 @@ async fn health() -> impl IntoResponse {
     (StatusCode::OK, "ok")
 }
 
+/// Stream state-change notifications as they happen via SSE.
+async fn state_events(
+    State(app): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = app.events.load().subscribe();
+    let stream = BroadcastStream::new(rx).filter_map(|msg| match msg {
+        Ok(event) => match serde_json::to_string(&event) {
+            Ok(json) => Some(Ok(Event::default().data(json))),
+            Err(e) => {
+                error!("failed to serialize state event: {}", e);
+                None
+            }
+        },
+        // A lagging receiver just misses the events it fell behind on; keep
+        // the stream alive rather than terminating it.
+        Err(BroadcastStreamRecvError::Lagged(_)) => None,
+    });
+
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    )
+}
+
 async fn get_state(State(app): State<AppState>, Path(key): Path<String>) -> impl IntoResponse {
     if key.is_empty() {
         return (StatusCode::BAD_REQUEST, Body::from("missing key")).into_response();
     }
 
     match app.state_client.get_state(&app.store_name, &key).await {
-        Ok(Some(value)) => (
-            StatusCode::OK,
-            [(header::CONTENT_TYPE, "application/octet-stream")],
-            Body::from(value),
-        )
-            .into_response(),
+        Ok(Some((value, etag))) => {
+            let mut headers = vec![(header::CONTENT_TYPE, "application/octet-stream".to_string())];
+            if let Some(etag) = etag {
+                headers.push((header::ETAG, etag));
+            }
+            (StatusCode::OK, headers, Body::from(value)).into_response()
+        }
         Ok(None) => (StatusCode::NOT_FOUND, Body::from("not found")).into_response(),
         Err(e) => {
             error!("get state failed: key={} err={}", key, e);
@@ -148,21 +374,45 @@ async fn get_state(State(app): State<AppState>, Path(key): Path<String>) -> impl
     }
 }
 
+/// Picks the concurrency semantics implied by an `If-Match` header: with an
+/// ETag to check, first-write-wins; with none, last-write-wins.
+fn concurrency_for(if_match: &Option<String>) -> ConcurrencyMode {
+    if if_match.is_some() {
+        ConcurrencyMode::FirstWriteWins
+    } else {
+        ConcurrencyMode::LastWriteWins
+    }
+}
+
 async fn save_state(
     State(app): State<AppState>,
     Path(key): Path<String>,
+    headers: axum::http::HeaderMap,
     body: axum::body::Bytes,
 ) -> impl IntoResponse {
     if key.is_empty() {
         return (StatusCode::BAD_REQUEST, Body::from("missing key")).into_response();
     }
 
+    let if_match = header_str(&headers, header::IF_MATCH);
+    let concurrency = concurrency_for(&if_match);
+    let len = body.len();
     match app
         .state_client
-        .save_state(&app.store_name, &key, body.to_vec())
+        .save_state_with_etag(&app.store_name, &key, body.to_vec(), if_match, concurrency)
         .await
     {
-        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Ok(()) => {
+            let _ = app.events.load().send(StateEvent {
+                key,
+                op: StateOp::Save,
+                len,
+            });
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(StateWriteError::EtagMismatch) => {
+            (StatusCode::PRECONDITION_FAILED, Body::from("etag mismatch")).into_response()
+        }
         Err(e) => {
             error!("save state failed: key={} err={}", key, e);
             (
@@ -174,13 +424,33 @@ async fn save_state(
     }
 }
 
-async fn delete_state(State(app): State<AppState>, Path(key): Path<String>) -> impl IntoResponse {
+async fn delete_state(
+    State(app): State<AppState>,
+    Path(key): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> impl IntoResponse {
     if key.is_empty() {
         return (StatusCode::BAD_REQUEST, Body::from("missing key")).into_response();
     }
 
-    match app.state_client.delete_state(&app.store_name, &key).await {
-        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+    let if_match = header_str(&headers, header::IF_MATCH);
+    let concurrency = concurrency_for(&if_match);
+    match app
+        .state_client
+        .delete_state_with_etag(&app.store_name, &key, if_match, concurrency)
+        .await
+    {
+        Ok(()) => {
+            let _ = app.events.load().send(StateEvent {
+                key,
+                op: StateOp::Delete,
+                len: 0,
+            });
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(StateWriteError::EtagMismatch) => {
+            (StatusCode::PRECONDITION_FAILED, Body::from("etag mismatch")).into_response()
+        }
         Err(e) => {
             error!("delete state failed: key={} err={}", key, e);
             (
@@ -191,3 +461,113 @@ async fn delete_state(State(app): State<AppState>, Path(key): Path<String>) -> i
         }
     }
 }
+
+fn header_str(headers: &axum::http::HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Wire representation of a [`TransactionOp`]; values travel base64-encoded
+/// over JSON, matching the bulk endpoint, since they are arbitrary bytes
+/// rather than UTF-8 text.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum TransactionOpDto {
+    Upsert { key: String, value: String },
+    Delete { key: String },
+}
+
+impl TryFrom<TransactionOpDto> for TransactionOp {
+    type Error = base64::DecodeError;
+
+    fn try_from(dto: TransactionOpDto) -> Result<Self, Self::Error> {
+        match dto {
+            TransactionOpDto::Upsert { key, value } => Ok(TransactionOp::Upsert {
+                key,
+                value: BASE64_STANDARD.decode(value)?,
+            }),
+            TransactionOpDto::Delete { key } => Ok(TransactionOp::Delete { key }),
+        }
+    }
+}
+
+async fn bulk_get_state(
+    State(app): State<AppState>,
+    Json(keys): Json<Vec<String>>,
+) -> impl IntoResponse {
+    match app.state_client.get_bulk_state(&app.store_name, &keys).await {
+        Ok(values) => {
+            // Values are arbitrary bytes (see the octet-stream single-key
+            // path), so base64-encode them for JSON rather than lossily
+            // reinterpreting them as UTF-8 text.
+            let values: Vec<Option<String>> = values
+                .into_iter()
+                .map(|value| value.map(|bytes| BASE64_STANDARD.encode(bytes)))
+                .collect();
+            (StatusCode::OK, Json(values)).into_response()
+        }
+        Err(e) => {
+            error!("bulk get state failed: err={}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Body::from("internal error"),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn execute_transaction(
+    State(app): State<AppState>,
+    Json(ops): Json<Vec<TransactionOpDto>>,
+) -> impl IntoResponse {
+    let ops: Vec<TransactionOp> = match ops.into_iter().map(TransactionOp::try_from).collect() {
+        Ok(ops) => ops,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Body::from(format!("invalid base64 value: {e}")),
+            )
+                .into_response()
+        }
+    };
+    let events: Vec<StateEvent> = ops
+        .iter()
+        .map(|op| match op {
+            TransactionOp::Upsert { key, value } => StateEvent {
+                key: key.clone(),
+                op: StateOp::Save,
+                len: value.len(),
+            },
+            TransactionOp::Delete { key } => StateEvent {
+                key: key.clone(),
+                op: StateOp::Delete,
+                len: 0,
+            },
+        })
+        .collect();
+
+    match app
+        .state_client
+        .execute_state_transaction(&app.store_name, ops)
+        .await
+    {
+        Ok(()) => {
+            let sender = app.events.load();
+            for event in events {
+                let _ = sender.send(event);
+            }
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            error!("execute state transaction failed: err={}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Body::from("internal error"),
+            )
+                .into_response()
+        }
+    }
+}
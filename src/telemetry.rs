@@ -9,8 +9,14 @@ use tracing::info;
 
 /// Initialize OpenTelemetry trace and metric providers.
 /// When `endpoint` is None or empty, uses no-op providers.
+/// `metric_export_interval_secs` sets the `PeriodicReader`'s export interval;
+/// call [`reload_meter_interval`] to change it later without restarting.
 /// Returns a shutdown function to flush and close exporters.
-pub fn init(endpoint: Option<&str>, service_name: &str) -> Box<dyn FnOnce() + Send> {
+pub fn init(
+    endpoint: Option<&str>,
+    service_name: &str,
+    metric_export_interval_secs: u64,
+) -> Box<dyn FnOnce() + Send> {
     let endpoint = endpoint.and_then(|s| {
         let s = s.trim();
         if s.is_empty() {
@@ -51,7 +57,8 @@ pub fn init(endpoint: Option<&str>, service_name: &str) -> Box<dyn FnOnce() + Se
     };
 
     // Initialize meter (metrics) - global owns it; provider will flush on drop
-    let meter_initialized = match init_meter(&metrics_endpoint, resource) {
+    let meter_initialized = match init_meter(&metrics_endpoint, resource, metric_export_interval_secs)
+    {
         Ok(mp) => {
             global::set_meter_provider(mp);
             true
@@ -71,6 +78,25 @@ pub fn init(endpoint: Option<&str>, service_name: &str) -> Box<dyn FnOnce() + Se
     })
 }
 
+/// Rebuild the meter provider with a new export interval and install it as
+/// the global meter provider. The provider `init` installed flushes on drop
+/// when replaced. No-op when `endpoint` is `None` or empty.
+pub fn reload_meter_interval(endpoint: Option<&str>, service_name: &str, metric_export_interval_secs: u64) {
+    let Some(endpoint) = endpoint.map(str::trim).filter(|s| !s.is_empty()) else {
+        return;
+    };
+    let metrics_endpoint = format!("{}/v1/metrics", ensure_http(endpoint));
+    let resource = Resource::new([opentelemetry::KeyValue::new(
+        "service.name",
+        service_name.to_string(),
+    )]);
+
+    match init_meter(&metrics_endpoint, resource, metric_export_interval_secs) {
+        Ok(mp) => global::set_meter_provider(mp),
+        Err(e) => tracing::warn!("failed to reload meter provider: {}", e),
+    }
+}
+
 fn init_tracer(
     endpoint: &str,
     resource: Resource,
@@ -92,6 +118,7 @@ fn init_tracer(
 fn init_meter(
     endpoint: &str,
     resource: Resource,
+    export_interval_secs: u64,
 ) -> Result<opentelemetry_sdk::metrics::SdkMeterProvider, Box<dyn std::error::Error + Send + Sync>>
 {
     let exporter = opentelemetry_otlp::MetricExporter::builder()
@@ -104,7 +131,7 @@ fn init_meter(
         exporter,
         opentelemetry_sdk::runtime::Tokio,
     )
-    .with_interval(std::time::Duration::from_secs(10))
+    .with_interval(std::time::Duration::from_secs(export_interval_secs))
     .build();
 
     let provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()